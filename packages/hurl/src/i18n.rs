@@ -0,0 +1,125 @@
+/*
+ * Hurl (https://hurl.dev)
+ * Copyright (C) 2023 Orange
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *          http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ */
+//! Localization of Hurl's CLI diagnostics, built on Fluent (FTL).
+//!
+//! Messages are embedded `.ftl` resources keyed by message id. The active locale is selected
+//! from `--lang`, then `LC_MESSAGES`, then `LANG`, and falls back to `en-US` whenever a locale
+//! or a message id is missing, so the CLI always has something reasonable to print.
+//!
+//! Today `en-US` is the only bundle shipped, so `message` always falls back to it regardless of
+//! the selected locale: the `active`/`by_locale` split exists so that adding a translation is
+//! just inserting another `.ftl` resource into `by_locale` in [`init`], with no change to the
+//! selection or fallback logic in [`message`].
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+const FALLBACK_LOCALE: &str = "en-US";
+const EN_US_FTL: &str = include_str!("i18n/locales/en-US.ftl");
+
+struct Bundles {
+    active: String,
+    by_locale: HashMap<String, FluentBundle<FluentResource>>,
+}
+
+static BUNDLES: OnceLock<Bundles> = OnceLock::new();
+
+/// Selects the active locale and loads the message bundles. Should be called once, early in
+/// `main`, before any call to [`message`]. `lang_override` (the `--lang` flag) takes precedence
+/// over the `LC_MESSAGES`/`LANG` environment variables.
+pub fn init(lang_override: Option<&str>) {
+    let active = lang_override
+        .map(str::to_string)
+        .or_else(|| env::var("LC_MESSAGES").ok())
+        .or_else(|| env::var("LANG").ok())
+        .map(|raw| normalize_locale(&raw))
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string());
+
+    let mut by_locale = HashMap::new();
+    by_locale.insert(
+        FALLBACK_LOCALE.to_string(),
+        build_bundle(FALLBACK_LOCALE, EN_US_FTL),
+    );
+
+    let _ = BUNDLES.set(Bundles { active, by_locale });
+}
+
+/// Keeps only the language/region part of a POSIX locale string, e.g. `fr_FR.UTF-8` -> `fr-FR`.
+fn normalize_locale(raw: &str) -> String {
+    raw.split('.').next().unwrap_or(raw).replace('_', "-")
+}
+
+fn build_bundle(locale: &str, source: &str) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = locale.parse().unwrap_or_else(|_| {
+        FALLBACK_LOCALE
+            .parse()
+            .expect("en-US is a valid language id")
+    });
+    let mut bundle = FluentBundle::new(vec![langid]);
+    // Without this, `format_pattern` wraps every interpolated `{ $var }` in U+2068/U+2069 bidi
+    // isolation marks. Those are invisible in a terminal but land in the output bytes, breaking
+    // exact-match comparisons and grep/pipe consumers of `$filename`, `$total`, etc.
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(res, _)| res);
+    bundle
+        .add_resource(resource)
+        .expect("built-in ftl resources are well-formed");
+    bundle
+}
+
+/// Formats `id` with `args` using the active locale, falling back to `en-US` when the locale or
+/// the message id isn't found there, and finally to the bare id if `en-US` doesn't have it
+/// either (it always should, since it's the bundle every other locale falls back to).
+pub fn message(id: &str, args: &[(&str, &str)]) -> String {
+    let bundles = BUNDLES.get_or_init(|| {
+        let mut by_locale = HashMap::new();
+        by_locale.insert(
+            FALLBACK_LOCALE.to_string(),
+            build_bundle(FALLBACK_LOCALE, EN_US_FTL),
+        );
+        Bundles {
+            active: FALLBACK_LOCALE.to_string(),
+            by_locale,
+        }
+    });
+
+    let mut fluent_args = FluentArgs::new();
+    for (key, value) in args {
+        fluent_args.set(*key, FluentValue::from(*value));
+    }
+
+    for locale in [bundles.active.as_str(), FALLBACK_LOCALE] {
+        let Some(bundle) = bundles.by_locale.get(locale) else {
+            continue;
+        };
+        let Some(msg) = bundle.get_message(id) else {
+            continue;
+        };
+        let Some(pattern) = msg.value() else {
+            continue;
+        };
+        let mut errors = vec![];
+        return bundle
+            .format_pattern(pattern, Some(&fluent_args), &mut errors)
+            .into_owned();
+    }
+    id.to_string()
+}
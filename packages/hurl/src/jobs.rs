@@ -0,0 +1,191 @@
+/*
+ * Hurl (https://hurl.dev)
+ * Copyright (C) 2023 Orange
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *          http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ */
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use hurl::output;
+use hurl::util::logger::{BaseLogger, LoggerBuilder};
+
+use crate::{cli, execute, i18n, unwrap_or_exit, HurlRun};
+use crate::{EXIT_ERROR_PARSING, EXIT_ERROR_RUNTIME};
+
+/// Runs `filenames` on a bounded pool of `jobs` worker threads and returns the collected runs,
+/// in the same order as `filenames`, as if they had run sequentially.
+///
+/// Each worker builds its logger with `.buffered(true)`: instead of writing to the terminal as
+/// the file runs, the logger records its output in memory. Once every run has completed, we
+/// flush each run's buffered output in the original file order, so two files running
+/// concurrently never have their progress lines interleaved.
+pub fn run_files(
+    filenames: &[String],
+    cli_options: &cli::CliOptions,
+    current_dir: &Path,
+    color: bool,
+    verbose: bool,
+    jobs: usize,
+    base_logger: &BaseLogger,
+) -> Vec<HurlRun> {
+    let total = filenames.len();
+    let next_index = AtomicUsize::new(0);
+    let slots: Mutex<Vec<Option<(HurlRun, String)>>> =
+        Mutex::new((0..total).map(|_| None).collect());
+    let worker_count = worker_count(jobs, total);
+
+    std::thread::scope(|scope| {
+        for _ in 0..worker_count {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::SeqCst);
+                if index >= total {
+                    break;
+                }
+                let filename = &filenames[index];
+
+                if filename != "-" && !Path::new(filename).exists() {
+                    let message = i18n::message("cannot-access", &[("filename", filename)]);
+                    base_logger.error(&message);
+                    std::process::exit(EXIT_ERROR_PARSING);
+                }
+                let content = match cli::read_to_string(filename) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        base_logger.error(&e.to_string());
+                        std::process::exit(EXIT_ERROR_PARSING);
+                    }
+                };
+
+                let logger = LoggerBuilder::new()
+                    .filename(filename)
+                    .color(color)
+                    .verbose(verbose)
+                    .test(cli_options.test)
+                    // A live progress bar across concurrent files would itself interleave, so
+                    // `--jobs N > 1` always runs without one.
+                    .progress_bar(false)
+                    .buffered(true)
+                    .build();
+
+                logger.test_running(index + 1, total);
+
+                let hurl_result = execute(&content, filename, current_dir, cli_options, &logger);
+                let hurl_result = match hurl_result {
+                    Ok(h) => h,
+                    Err(_) => std::process::exit(EXIT_ERROR_PARSING),
+                };
+                logger.test_completed(&hurl_result);
+
+                let success = hurl_result.success;
+                let output_body = success
+                    && !cli_options.interactive
+                    && matches!(cli_options.output_type, cli::OutputType::ResponseBody);
+                if output_body {
+                    let include_headers = cli_options.include;
+                    let result = output::write_body(
+                        &hurl_result,
+                        filename,
+                        include_headers,
+                        color,
+                        &cli_options.output,
+                        &logger,
+                    );
+                    unwrap_or_exit(result, EXIT_ERROR_RUNTIME, base_logger);
+                }
+                if matches!(cli_options.output_type, cli::OutputType::Json) {
+                    let result =
+                        output::write_json(&hurl_result, &content, filename, &cli_options.output);
+                    unwrap_or_exit(result, EXIT_ERROR_RUNTIME, base_logger);
+                }
+
+                let run = HurlRun {
+                    content,
+                    filename: filename.to_string(),
+                    hurl_result,
+                };
+                slots.lock().unwrap()[index] = Some((run, logger.buffer()));
+            });
+        }
+    });
+
+    flush_in_order(slots.into_inner().unwrap())
+}
+
+/// How many worker threads to use: never more than requested, never more than one per file, and
+/// never zero (so `--jobs 0` doesn't hang).
+fn worker_count(jobs: usize, total: usize) -> usize {
+    jobs.min(total).max(1)
+}
+
+/// Flushes every slot's buffered logger output in original file order, then returns the runs in
+/// that same order.
+fn flush_in_order(slots: Vec<Option<(HurlRun, String)>>) -> Vec<HurlRun> {
+    slots
+        .into_iter()
+        .map(|slot| {
+            let (run, buffer) = slot.expect("every index is produced by exactly one worker");
+            // The serial logger writes its progress to stderr; flushing the buffered output the
+            // same way keeps `--jobs N > 1` from changing which stream test output lands on.
+            eprint!("{buffer}");
+            run
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hurl::runner::{EntryResult, HurlResult};
+
+    #[test]
+    fn worker_count_never_exceeds_total_files_or_requested_jobs() {
+        assert_eq!(worker_count(8, 3), 3);
+        assert_eq!(worker_count(2, 8), 2);
+    }
+
+    #[test]
+    fn worker_count_is_never_zero() {
+        assert_eq!(worker_count(0, 5), 1);
+        assert_eq!(worker_count(4, 0), 1);
+    }
+
+    fn dummy_run(filename: &str) -> HurlRun {
+        HurlRun {
+            content: String::new(),
+            filename: filename.to_string(),
+            hurl_result: HurlResult {
+                entries: Vec::<EntryResult>::new(),
+                time_in_ms: 0,
+                success: true,
+                cookies: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn flush_in_order_preserves_original_file_order() {
+        let slots = vec![
+            Some((dummy_run("a.hurl"), String::new())),
+            Some((dummy_run("b.hurl"), String::new())),
+            Some((dummy_run("c.hurl"), String::new())),
+        ];
+
+        let runs = flush_in_order(slots);
+
+        let filenames: Vec<&str> = runs.iter().map(|r| r.filename.as_str()).collect();
+        assert_eq!(filenames, vec!["a.hurl", "b.hurl", "c.hurl"]);
+    }
+}
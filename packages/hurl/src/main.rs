@@ -16,6 +16,11 @@
  *
  */
 mod cli;
+mod i18n;
+mod jobs;
+mod snapshot;
+mod time_report;
+mod watch;
 
 use std::collections::HashMap;
 use std::env;
@@ -40,12 +45,12 @@ const EXIT_ERROR_UNDEFINED: i32 = 127;
 
 /// Structure that stores the result of an Hurl file execution, and the content of the file.
 #[derive(Clone, Debug, PartialEq, Eq)]
-struct HurlRun {
+pub(crate) struct HurlRun {
     /// Source string for this [`HurlFile`]
-    content: String,
+    pub(crate) content: String,
     /// Filename of the content
-    filename: String,
-    hurl_result: HurlResult,
+    pub(crate) filename: String,
+    pub(crate) hurl_result: HurlResult,
 }
 
 /// Executes Hurl entry point.
@@ -62,6 +67,10 @@ fn main() {
     let mut app = cli::app(&version_info);
     let matches = app.clone().get_matches();
 
+    // The `--lang` flag overrides `LC_MESSAGES`/`LANG` for selecting which Fluent bundle to use
+    // for the diagnostics printed below; this must run before the first call to `i18n::message`.
+    i18n::init(cli::get_string(&matches, "lang").as_deref());
+
     // We create a basic logger that can just display info, warning or error generic messages.
     // We'll use a more advanced logger for rich error report when running Hurl files.
     let verbose = cli::has_flag(&matches, "verbose")
@@ -89,22 +98,125 @@ fn main() {
     }
 
     let progress_bar = cli_options.test && !verbose && !is_ci() && atty::is(Stream::Stderr);
+    // We capture the current directory once, at startup, and reuse it for every run (including
+    // re-runs triggered by `--watch`), so relative paths stay stable even if a run `chdir`s.
     let current_dir = env::current_dir();
     let current_dir = unwrap_or_exit(current_dir, EXIT_ERROR_UNDEFINED, &base_logger);
     let current_dir = current_dir.as_path();
 
     let start = Instant::now();
+
+    if cli::has_flag(&matches, "watch") {
+        watch::watch(&filenames, current_dir, || {
+            // Each re-run gets its own clock: reusing the startup `start` would make the
+            // reported duration grow across re-runs instead of reflecting this run alone.
+            let run_start = Instant::now();
+            let runs = run_files(&filenames, &cli_options, current_dir, color, verbose, progress_bar, &base_logger);
+            let referenced = runs.iter().flat_map(|r| referenced_files(&r.content)).collect();
+            if cli_options.test {
+                let duration = run_start.elapsed().as_millis();
+                let summary = get_summary(&runs, duration);
+                base_logger.info(summary.as_str());
+            }
+            referenced
+        });
+        return;
+    }
+
+    // A `--jobs` greater than 1 runs files on a bounded thread pool instead of the sequential
+    // loop; `--interactive` needs a single, uninterrupted terminal session so it always forces
+    // serial mode regardless of `--jobs`.
+    let runs = if cli_options.jobs > 1 && !cli_options.interactive {
+        jobs::run_files(
+            &filenames,
+            &cli_options,
+            current_dir,
+            color,
+            verbose,
+            cli_options.jobs,
+            &base_logger,
+        )
+    } else {
+        run_files(
+            &filenames,
+            &cli_options,
+            current_dir,
+            color,
+            verbose,
+            progress_bar,
+            &base_logger,
+        )
+    };
+
+    if let Some(filename) = cli_options.junit_file {
+        base_logger.debug(i18n::message("writing-junit-report", &[("filename", &filename)]).as_str());
+        let result = create_junit_report(&runs, &filename);
+        unwrap_or_exit(result, EXIT_ERROR_UNDEFINED, &base_logger);
+    }
+
+    if let Some(dir) = cli_options.html_dir {
+        base_logger.debug(i18n::message("writing-html-report", &[("dir", &dir.display().to_string())]).as_str());
+        let result = create_html_report(&runs, &dir);
+        unwrap_or_exit(result, EXIT_ERROR_UNDEFINED, &base_logger);
+    }
+
+    if let Some(filename) = cli_options.cookie_output_file {
+        base_logger.debug(i18n::message("writing-cookies", &[("filename", &filename)]).as_str());
+        let result = create_cookies_file(&runs, &filename);
+        unwrap_or_exit(result, EXIT_ERROR_UNDEFINED, &base_logger);
+    }
+
+    if cli_options.test {
+        let duration = start.elapsed().as_millis();
+        let summary = get_summary(&runs, duration);
+        base_logger.info(summary.as_str());
+    }
+
+    // Snapshot-testing pins response bodies to a `.hurl.snap` file next to the source: a
+    // mismatch fails the run just like an inline assert would, unless `--bless` is set to
+    // rewrite the snapshot instead.
+    let snapshot_mismatches = if cli_options.snapshot {
+        snapshot::check(&runs, cli_options.bless, &base_logger)
+    } else {
+        0
+    };
+
+    if let Some(format) = cli_options.time_report {
+        println!("{}", time_report::render(&runs, format));
+    }
+
+    let mut code = exit_code(&runs);
+    if snapshot_mismatches > 0 && code == EXIT_OK {
+        code = EXIT_ERROR_ASSERT;
+    }
+    std::process::exit(code);
+}
+
+/// Runs every `filenames` sequentially and returns the collected runs.
+///
+/// This is the body of the historical single-pass `main` loop, extracted so it can be called
+/// again on every re-run triggered by `--watch`.
+#[allow(clippy::too_many_arguments)]
+fn run_files(
+    filenames: &[String],
+    cli_options: &cli::CliOptions,
+    current_dir: &Path,
+    color: bool,
+    verbose: bool,
+    progress_bar: bool,
+    base_logger: &BaseLogger,
+) -> Vec<HurlRun> {
     let mut runs = vec![];
 
     for (current, filename) in filenames.iter().enumerate() {
         // We check the input file existence and check that we can read its contents.
         // Once the preconditions succeed, we can parse the Hurl file, and run it.
         if filename != "-" && !Path::new(filename).exists() {
-            let message = format!("hurl: cannot access '{filename}': No such file or directory");
-            exit_with_error(&message, EXIT_ERROR_PARSING, &base_logger);
+            let message = i18n::message("cannot-access", &[("filename", filename)]);
+            exit_with_error(&message, EXIT_ERROR_PARSING, base_logger);
         }
         let content = cli::read_to_string(filename);
-        let content = unwrap_or_exit(content, EXIT_ERROR_PARSING, &base_logger);
+        let content = unwrap_or_exit(content, EXIT_ERROR_PARSING, base_logger);
 
         let logger = LoggerBuilder::new()
             .filename(filename)
@@ -118,7 +230,7 @@ fn main() {
         logger.test_running(current + 1, total);
 
         // Run our Hurl file now
-        let hurl_result = execute(&content, filename, current_dir, &cli_options, &logger);
+        let hurl_result = execute(&content, filename, current_dir, cli_options, &logger);
         let hurl_result = match hurl_result {
             Ok(h) => h,
             Err(_) => std::process::exit(EXIT_ERROR_PARSING),
@@ -140,12 +252,12 @@ fn main() {
                 &cli_options.output,
                 &logger,
             );
-            unwrap_or_exit(result, EXIT_ERROR_RUNTIME, &base_logger);
+            unwrap_or_exit(result, EXIT_ERROR_RUNTIME, base_logger);
         }
 
         if matches!(cli_options.output_type, cli::OutputType::Json) {
             let result = output::write_json(&hurl_result, &content, filename, &cli_options.output);
-            unwrap_or_exit(result, EXIT_ERROR_RUNTIME, &base_logger);
+            unwrap_or_exit(result, EXIT_ERROR_RUNTIME, base_logger);
         }
 
         let run = HurlRun {
@@ -156,35 +268,29 @@ fn main() {
         runs.push(run);
     }
 
-    if let Some(filename) = cli_options.junit_file {
-        base_logger.debug(format!("Writing JUnit report to {filename}").as_str());
-        let result = create_junit_report(&runs, &filename);
-        unwrap_or_exit(result, EXIT_ERROR_UNDEFINED, &base_logger);
-    }
-
-    if let Some(dir) = cli_options.html_dir {
-        base_logger.debug(format!("Writing HTML report to {}", dir.display()).as_str());
-        let result = create_html_report(&runs, &dir);
-        unwrap_or_exit(result, EXIT_ERROR_UNDEFINED, &base_logger);
-    }
-
-    if let Some(filename) = cli_options.cookie_output_file {
-        base_logger.debug(format!("Writing cookies to {filename}").as_str());
-        let result = create_cookies_file(&runs, &filename);
-        unwrap_or_exit(result, EXIT_ERROR_UNDEFINED, &base_logger);
-    }
-
-    if cli_options.test {
-        let duration = start.elapsed().as_millis();
-        let summary = get_summary(&runs, duration);
-        base_logger.info(summary.as_str());
-    }
+    runs
+}
 
-    std::process::exit(exit_code(&runs));
+/// Returns the paths referenced by a Hurl file's `content` that should also be watched in
+/// `--watch` mode: bodies read from disk and `file,` multipart parts.
+fn referenced_files(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .flat_map(|line| {
+            // A `file,` reference can be a whole line (a body read from disk) or just one token
+            // on a `field: file,name;` multipart part line, so we look at whitespace-separated
+            // tokens rather than the line as a whole.
+            line.split_whitespace().filter_map(|token| {
+                let rest = token.strip_prefix("file,")?;
+                let path = rest.trim_end_matches(';');
+                (!path.is_empty()).then(|| path.to_string())
+            })
+        })
+        .collect()
 }
 
 /// Runs a Hurl `content` and returns a result.
-fn execute(
+pub(crate) fn execute(
     content: &str,
     filename: &str,
     current_dir: &Path,
@@ -209,7 +315,7 @@ fn init_colored() {
 }
 
 /// Unwraps a `result` or exit with message.
-fn unwrap_or_exit<T, E>(result: Result<T, E>, code: i32, logger: &BaseLogger) -> T
+pub(crate) fn unwrap_or_exit<T, E>(result: Result<T, E>, code: i32, logger: &BaseLogger) -> T
 where
     E: std::fmt::Display,
 {
@@ -348,13 +454,28 @@ fn get_summary(runs: &[HurlRun], duration: u128) -> String {
     let success_percent = 100.0 * success as f32 / total as f32;
     let failed = total - success;
     let failed_percent = 100.0 * failed as f32 / total as f32;
-    format!(
-        "--------------------------------------------------------------------------------\n\
-             Executed files:  {total}\n\
-             Succeeded files: {success} ({success_percent:.1}%)\n\
-             Failed files:    {failed} ({failed_percent:.1}%)\n\
-             Duration:        {duration} ms\n"
-    )
+
+    let total = total.to_string();
+    let success = success.to_string();
+    let success_percent = format!("{success_percent:.1}");
+    let failed = failed.to_string();
+    let failed_percent = format!("{failed_percent:.1}");
+    let duration = duration.to_string();
+
+    let lines = [
+        i18n::message("summary-separator", &[]),
+        i18n::message("summary-executed", &[("total", &total)]),
+        i18n::message(
+            "summary-succeeded",
+            &[("success", &success), ("percent", &success_percent)],
+        ),
+        i18n::message(
+            "summary-failed",
+            &[("failed", &failed), ("percent", &failed_percent)],
+        ),
+        i18n::message("summary-duration", &[("duration", &duration)]),
+    ];
+    lines.join("\n") + "\n"
 }
 
 /// Whether or not this running in a Continuous Integration environment.
@@ -424,4 +545,25 @@ pub mod tests {
             Duration:        200 ms\n"
         );
     }
+
+    #[test]
+    fn referenced_files_extracts_file_parts_and_ignores_other_lines() {
+        let content = "\
+POST https://example.org/upload
+[Multipart]
+field: file,report.pdf;
+field2: value
+```
+file,body.json;
+```";
+        assert_eq!(
+            referenced_files(content),
+            vec!["report.pdf".to_string(), "body.json".to_string()]
+        );
+    }
+
+    #[test]
+    fn referenced_files_returns_empty_when_none_present() {
+        assert_eq!(referenced_files("GET https://example.org\nHTTP 200"), Vec::<String>::new());
+    }
 }
@@ -0,0 +1,215 @@
+/*
+ * Hurl (https://hurl.dev)
+ * Copyright (C) 2023 Orange
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *          http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ */
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use hurl::util::logger::BaseLogger;
+
+use crate::HurlRun;
+
+/// Returns the snapshot file path for a given Hurl source `filename`: `foo.hurl` becomes
+/// `foo.hurl.snap`, next to the source file.
+fn snapshot_path(filename: &str) -> PathBuf {
+    PathBuf::from(format!("{filename}.snap"))
+}
+
+/// Builds the deterministic snapshot content for a run: the response body of every entry's
+/// last call, separated by an `--- entry N ---` marker so a diff stays readable when a file
+/// has more than one entry.
+fn render_snapshot(run: &HurlRun) -> String {
+    let mut snapshot = String::new();
+    for entry in &run.hurl_result.entries {
+        let Some(call) = entry.calls.last() else {
+            continue;
+        };
+        snapshot.push_str(&format!("--- entry {} ---\n", entry.entry_index));
+        snapshot.push_str(&String::from_utf8_lossy(&call.response.body));
+        if !snapshot.ends_with('\n') {
+            snapshot.push('\n');
+        }
+    }
+    snapshot
+}
+
+/// Checks every successful run against its stored snapshot: writes a snapshot when one doesn't
+/// exist yet, rewrites a mismatching one when `bless` is set, otherwise prints a unified diff
+/// and fails. Returns the number of snapshots that still mismatch after this call.
+pub fn check(runs: &[HurlRun], bless: bool, base_logger: &BaseLogger) -> usize {
+    let mut mismatches = 0;
+    let mut blessed = 0;
+
+    for run in runs {
+        if !run.hurl_result.success {
+            continue;
+        }
+        let path = snapshot_path(&run.filename);
+        let actual = render_snapshot(run);
+
+        match fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) if bless => match fs::write(&path, &actual) {
+                Ok(()) => blessed += 1,
+                Err(e) => {
+                    mismatches += 1;
+                    report_write_error(&path, &e, base_logger);
+                }
+            },
+            Ok(expected) => {
+                mismatches += 1;
+                print_diff(&path, &expected, &actual, base_logger);
+            }
+            Err(_) => {
+                if let Err(e) = fs::write(&path, &actual) {
+                    mismatches += 1;
+                    report_write_error(&path, &e, base_logger);
+                }
+            }
+        }
+    }
+
+    if bless && blessed > 0 {
+        base_logger.info(format!("{blessed} snapshot(s) updated").as_str());
+    }
+    mismatches
+}
+
+/// Reports a snapshot file that couldn't be written, so a permissions or read-only-dir failure
+/// surfaces as an error and a mismatch instead of being swallowed.
+fn report_write_error(path: &Path, error: &std::io::Error, base_logger: &BaseLogger) {
+    base_logger.error(format!("Can't write snapshot {}: {error}", path.display()).as_str());
+}
+
+/// Prints a unified, colored line-by-line diff between the stored snapshot and the live
+/// response, similar to compiletest's `uidiff`.
+fn print_diff(path: &Path, expected: &str, actual: &str, base_logger: &BaseLogger) {
+    base_logger.error(format!("Snapshot mismatch: {}", path.display()).as_str());
+    for line in diff_lines(expected, actual) {
+        println!("{line}");
+    }
+}
+
+/// Builds the unified, colored line-by-line diff between `expected` and `actual`, one rendered
+/// line per entry: unchanged lines are prefixed with a space, removed lines are red with a `-`,
+/// added lines are green with a `+`.
+fn diff_lines(expected: &str, actual: &str) -> Vec<String> {
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let max = expected_lines.len().max(actual_lines.len());
+
+    let mut lines = Vec::with_capacity(max);
+    for i in 0..max {
+        match (expected_lines.get(i), actual_lines.get(i)) {
+            (Some(e), Some(a)) if e == a => lines.push(format!(" {e}")),
+            (Some(e), Some(a)) => {
+                lines.push(format!("-{e}").red().to_string());
+                lines.push(format!("+{a}").green().to_string());
+            }
+            (Some(e), None) => lines.push(format!("-{e}").red().to_string()),
+            (None, Some(a)) => lines.push(format!("+{a}").green().to_string()),
+            (None, None) => {}
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hurl::runner::{Call, EntryResult, Request, Response};
+
+    fn dummy_entry(entry_index: usize, body: &[u8]) -> EntryResult {
+        EntryResult {
+            entry_index,
+            calls: vec![Call {
+                request: Request {
+                    method: "GET".to_string(),
+                    url: "https://example.org".to_string(),
+                },
+                response: Response {
+                    body: body.to_vec(),
+                },
+            }],
+            captures: vec![],
+            asserts: vec![],
+            errors: vec![],
+            time_in_ms: 0,
+            compressed: false,
+        }
+    }
+
+    #[test]
+    fn snapshot_path_appends_snap_extension() {
+        assert_eq!(snapshot_path("foo.hurl"), PathBuf::from("foo.hurl.snap"));
+    }
+
+    #[test]
+    fn render_snapshot_marks_each_entry_and_appends_trailing_newline() {
+        let run = HurlRun {
+            content: String::new(),
+            filename: "foo.hurl".to_string(),
+            hurl_result: hurl::runner::HurlResult {
+                entries: vec![dummy_entry(0, b"{\"ok\":true}"), dummy_entry(1, b"done\n")],
+                time_in_ms: 0,
+                success: true,
+                cookies: vec![],
+            },
+        };
+
+        assert_eq!(
+            render_snapshot(&run),
+            "--- entry 0 ---\n{\"ok\":true}\n--- entry 1 ---\ndone\n"
+        );
+    }
+
+    #[test]
+    fn diff_lines_marks_unchanged_lines_with_a_leading_space() {
+        let lines = diff_lines("a\nb\nc", "a\nb\nc");
+        assert_eq!(lines, vec![" a", " b", " c"]);
+    }
+
+    #[test]
+    fn diff_lines_reports_extra_actual_lines_as_additions() {
+        let lines = diff_lines("a", "a\nb");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], " a");
+        assert!(lines[1].contains('+'));
+        assert!(lines[1].contains('b'));
+    }
+
+    #[test]
+    fn check_counts_a_mismatch_when_the_snapshot_cannot_be_written() {
+        // The parent directory doesn't exist, so writing the first snapshot fails; that must be
+        // reported as a mismatch rather than silently counted as success.
+        let run = HurlRun {
+            content: String::new(),
+            filename: "/no-such-dir-for-hurl-snapshot-tests/foo.hurl".to_string(),
+            hurl_result: hurl::runner::HurlResult {
+                entries: vec![dummy_entry(0, b"body")],
+                time_in_ms: 0,
+                success: true,
+                cookies: vec![],
+            },
+        };
+        let base_logger = BaseLogger::new(false, false);
+
+        let mismatches = check(&[run], false, &base_logger);
+
+        assert_eq!(mismatches, 1);
+    }
+}
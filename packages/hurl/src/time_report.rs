@@ -0,0 +1,222 @@
+/*
+ * Hurl (https://hurl.dev)
+ * Copyright (C) 2023 Orange
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *          http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ */
+use crate::HurlRun;
+
+/// Output format for `--time-report`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Timing breakdown for a single entry, flattened out of its last libcurl call.
+struct EntryTiming {
+    filename: String,
+    entry_index: usize,
+    method: String,
+    url: String,
+    time_in_ms: u128,
+    compressed: bool,
+    dns_ms: Option<u128>,
+    connect_ms: Option<u128>,
+    tls_ms: Option<u128>,
+    transfer_ms: Option<u128>,
+}
+
+/// Flattens every entry of every run into a single, file-order list of timings.
+fn collect(runs: &[HurlRun]) -> Vec<EntryTiming> {
+    let mut timings = vec![];
+    for run in runs {
+        for entry in &run.hurl_result.entries {
+            let Some(call) = entry.calls.last() else {
+                continue;
+            };
+            let splits = call.timings.as_ref().map(|t| {
+                (
+                    t.name_lookup.as_millis(),
+                    t.connect.saturating_sub(t.name_lookup).as_millis(),
+                    t.app_connect.saturating_sub(t.connect).as_millis(),
+                    t.total.saturating_sub(t.start_transfer).as_millis(),
+                )
+            });
+            timings.push(EntryTiming {
+                filename: run.filename.clone(),
+                entry_index: entry.entry_index,
+                method: call.request.method.to_string(),
+                url: call.request.url.to_string(),
+                time_in_ms: entry.time_in_ms,
+                compressed: entry.compressed,
+                dns_ms: splits.map(|s| s.0),
+                connect_ms: splits.map(|s| s.1),
+                tls_ms: splits.map(|s| s.2),
+                transfer_ms: splits.map(|s| s.3),
+            });
+        }
+    }
+    timings
+}
+
+/// Renders a `--time-report` for `runs` in the given `format`.
+pub fn render(runs: &[HurlRun], format: Format) -> String {
+    let timings = collect(runs);
+    match format {
+        Format::Text => render_text(&timings),
+        Format::Json => render_json(&timings),
+    }
+}
+
+fn render_text(timings: &[EntryTiming]) -> String {
+    let mut sorted: Vec<&EntryTiming> = timings.iter().collect();
+    sorted.sort_by(|a, b| b.time_in_ms.cmp(&a.time_in_ms));
+
+    let mut report = String::new();
+    report.push_str("Slowest entries:\n");
+    for t in sorted {
+        report.push_str(&format!(
+            "{:>6} ms  {} {} {}#{}\n",
+            t.time_in_ms, t.method, t.url, t.filename, t.entry_index
+        ));
+    }
+    if let Some(peak_kb) = peak_memory_kb() {
+        report.push_str(&format!("Peak memory: {peak_kb} KB\n"));
+    }
+    report
+}
+
+fn render_json(timings: &[EntryTiming]) -> String {
+    let mut entries = Vec::with_capacity(timings.len());
+    for t in timings {
+        entries.push(format!(
+            "{{\"filename\":{},\"entry_index\":{},\"method\":{},\"url\":{},\"time_in_ms\":{},\
+             \"compressed\":{},\"dns_ms\":{},\"connect_ms\":{},\"tls_ms\":{},\"transfer_ms\":{}}}",
+            json_string(&t.filename),
+            t.entry_index,
+            json_string(&t.method),
+            json_string(&t.url),
+            t.time_in_ms,
+            t.compressed,
+            json_option(t.dns_ms),
+            json_option(t.connect_ms),
+            json_option(t.tls_ms),
+            json_option(t.transfer_ms),
+        ));
+    }
+    let peak_memory_kb = json_option(peak_memory_kb().map(u128::from));
+    format!(
+        "{{\"entries\":[{}],\"peak_memory_kb\":{peak_memory_kb}}}",
+        entries.join(",")
+    )
+}
+
+fn json_option(value: Option<u128>) -> String {
+    match value {
+        Some(v) => v.to_string(),
+        None => "null".to_string(),
+    }
+}
+
+/// Escapes `value` into a JSON string literal (quotes included). Beyond `\` and `"`, every
+/// control character is escaped too: a URL or method containing a raw newline or tab would
+/// otherwise produce invalid JSON.
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Samples the process' peak resident set size, in kilobytes, the same way rustc's
+/// `-Z time-passes` reports memory usage. Returns `None` on every target other than Linux, or
+/// when `/proc/self/status` can't be read.
+#[cfg(target_os = "linux")]
+fn peak_memory_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status.lines().find_map(|line| {
+        let rest = line.strip_prefix("VmHWM:")?;
+        rest.trim().split_whitespace().next()?.parse().ok()
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_memory_kb() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_escapes_quotes_and_backslashes() {
+        assert_eq!(json_string(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+
+    #[test]
+    fn json_string_escapes_control_characters() {
+        assert_eq!(json_string("a\nb\tc\rd"), r#""a\nb\tc\rd""#);
+        assert_eq!(json_string("a\u{1}b"), r#""a\u0001b""#);
+    }
+
+    #[test]
+    fn json_option_renders_null_for_none() {
+        assert_eq!(json_option(None), "null");
+        assert_eq!(json_option(Some(42)), "42");
+    }
+
+    #[test]
+    fn render_json_produces_the_documented_schema() {
+        let timings = vec![EntryTiming {
+            filename: "foo.hurl".to_string(),
+            entry_index: 0,
+            method: "GET".to_string(),
+            url: "https://example.org".to_string(),
+            time_in_ms: 12,
+            compressed: false,
+            dns_ms: Some(1),
+            connect_ms: Some(2),
+            tls_ms: None,
+            transfer_ms: Some(9),
+        }];
+
+        let json = render_json(&timings);
+
+        assert!(json.starts_with(r#"{"entries":[{"#));
+        assert!(json.contains(r#""filename":"foo.hurl""#));
+        assert!(json.contains(r#""entry_index":0"#));
+        assert!(json.contains(r#""method":"GET""#));
+        assert!(json.contains(r#""url":"https://example.org""#));
+        assert!(json.contains(r#""time_in_ms":12"#));
+        assert!(json.contains(r#""compressed":false"#));
+        assert!(json.contains(r#""dns_ms":1"#));
+        assert!(json.contains(r#""connect_ms":2"#));
+        assert!(json.contains(r#""tls_ms":null"#));
+        assert!(json.contains(r#""transfer_ms":9"#));
+        assert!(json.contains(r#""peak_memory_kb":"#));
+    }
+}
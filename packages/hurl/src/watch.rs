@@ -0,0 +1,130 @@
+/*
+ * Hurl (https://hurl.dev)
+ * Copyright (C) 2023 Orange
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *          http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ */
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+
+/// How long we wait after the first filesystem event before re-running, so that a burst of
+/// saves coming from an editor (or a `git checkout`) is coalesced into a single run.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Runs `filenames` once with `run_once`, then keeps re-running them every time one of the
+/// watched files (or one of the files they reference, e.g. a multipart `file,` part) changes,
+/// until the process is interrupted with Ctrl-C.
+///
+/// `current_dir` is captured once by the caller, before this function is entered, so relative
+/// paths keep resolving the same way on every re-run, even if a run happens to `chdir`.
+pub fn watch(filenames: &[String], current_dir: &Path, mut run_once: impl FnMut() -> Vec<String>) {
+    let (tx, rx) = channel();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("hurl: can't start watch mode: {e}");
+            return;
+        }
+    };
+
+    let mut watched = watch_paths(filenames, current_dir, &[]);
+    for path in &watched {
+        // Missing files (not yet created) simply can't be watched; they'll be picked up on the
+        // next run if a sibling file changes and we re-scan the referenced paths.
+        let _ = watcher.watch(path, RecursiveMode::NonRecursive);
+    }
+
+    loop {
+        clear_screen();
+        let referenced = run_once();
+        watched = watch_paths(filenames, current_dir, &referenced);
+        for path in &watched {
+            let _ = watcher.watch(path, RecursiveMode::NonRecursive);
+        }
+
+        // Wait for the first event, then drain anything else that arrives within `DEBOUNCE` so
+        // an editor's "write temp file + rename" dance only triggers one re-run.
+        match rx.recv() {
+            Ok(_) => {}
+            Err(_) => break,
+        }
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+/// Returns the absolute, deduped set of paths to watch: the input files themselves, plus any
+/// extra path referenced by the previous run (bodies read from disk, `file,` multipart parts).
+fn watch_paths(
+    filenames: &[String],
+    current_dir: &Path,
+    referenced: &[String],
+) -> HashSet<PathBuf> {
+    let mut paths = HashSet::new();
+    for filename in filenames {
+        if filename == "-" {
+            continue;
+        }
+        paths.insert(current_dir.join(filename));
+    }
+    for filename in referenced {
+        paths.insert(current_dir.join(filename));
+    }
+    paths
+}
+
+/// Clears the terminal screen, the same way `cargo watch` and `deno test --watch` do between
+/// re-runs, so the new run's output isn't visually mixed with the previous one.
+fn clear_screen() {
+    print!("\x1B[2J\x1B[1;1H");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watch_paths_resolves_filenames_against_current_dir() {
+        let current_dir = Path::new("/work");
+        let filenames = vec!["a.hurl".to_string(), "b.hurl".to_string()];
+
+        let paths = watch_paths(&filenames, current_dir, &[]);
+
+        assert_eq!(paths.len(), 2);
+        assert!(paths.contains(&PathBuf::from("/work/a.hurl")));
+        assert!(paths.contains(&PathBuf::from("/work/b.hurl")));
+    }
+
+    #[test]
+    fn watch_paths_skips_stdin_and_dedups_referenced_files() {
+        let current_dir = Path::new("/work");
+        let filenames = vec!["-".to_string(), "a.hurl".to_string()];
+        let referenced = vec!["a.hurl".to_string(), "body.json".to_string()];
+
+        let paths = watch_paths(&filenames, current_dir, &referenced);
+
+        assert_eq!(paths.len(), 2);
+        assert!(paths.contains(&PathBuf::from("/work/a.hurl")));
+        assert!(paths.contains(&PathBuf::from("/work/body.json")));
+    }
+}